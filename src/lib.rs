@@ -1,3 +1,7 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
 use std::mem;
 
 enum NodeOption<T> {
@@ -26,10 +30,22 @@ enum Sentry {
     }
 }
 
+/// A stable reference to an element previously inserted into a
+/// `FixedCapacityList`, as returned by `enqueue`
+///
+/// Internally this pairs a slot index with the generation the slot was on
+/// when the handle was issued. When a slot is freed and later reused by a
+/// new `enqueue`, its generation is bumped, so a handle into the old
+/// occupant is recognised as stale by `contains`/`remove` instead of
+/// silently referring to whatever now lives in that slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Handle(usize, u32);
+
 pub struct FixedCapacityList<T> {
     heap: Vec<NodeOption<T>>,
     list: Sentry,
     free: Option<usize>,
+    generation: Vec<u32>,
 }
 
 impl<T> FixedCapacityList<T> {
@@ -43,17 +59,21 @@ impl<T> FixedCapacityList<T> {
         FixedCapacityList {
             heap: heap,
             list: Sentry::Empty,
-            free: Some(0), 
+            free: Some(0),
+            generation: vec![0; capacity],
         }
     }
 
-    /// Add an element to the end of the list
-    /// 
+    /// Add an element to the end of the list, returning a `Handle` that can
+    /// later be used with `remove` to take the element back out again in
+    /// O(1), regardless of where it ends up in the list
+    ///
     /// # Panics
     /// if there is no remaining capacity
-    pub fn enqueue(&mut self, element: T) {
+    pub fn enqueue(&mut self, element: T) -> Handle {
         let free_index = self.free.expect("No remaining capacity");
         self.free = self.heap[free_index].expect_free();
+        self.generation[free_index] += 1;
         match self.list {
             // First item in empty list
             Sentry::Empty => {
@@ -84,47 +104,483 @@ impl<T> FixedCapacityList<T> {
                 }
             },
         }
+        Handle(free_index, self.generation[free_index])
+    }
+
+    /// Whether `handle` still refers to an element currently in the list
+    pub fn contains(&self, handle: Handle) -> bool {
+        let Handle(index, generation) = handle;
+        index < self.heap.len() &&
+            self.generation[index] == generation &&
+            match self.heap[index] {
+                NodeOption::Occupied { .. } => true,
+                NodeOption::Free(..) => false,
+            }
+    }
+
+    /// Returns a reference to the element referred to by `handle`, or
+    /// `None` if the handle is stale
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        if !self.contains(handle) {
+            return None;
+        }
+        match self.heap[handle.0] {
+            NodeOption::Occupied { ref value, .. } => Some(value),
+            NodeOption::Free(..) => None,
+        }
+    }
+
+    /// Returns the handle of the front (oldest) element, if any
+    pub fn front_handle(&self) -> Option<Handle> {
+        match self.list {
+            Sentry::Empty => None,
+            Sentry::Filled { first, .. } => Some(Handle(first, self.generation[first])),
+        }
+    }
+
+    /// Remove and return the element referred to by `handle` in O(1), no
+    /// matter where it sits in the list, by unlinking it and patching its
+    /// neighbours' `next`/`prev` pointers
+    ///
+    /// Returns `None` if the handle is stale (its element was already
+    /// removed, possibly with the slot since reused by another element).
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        if !self.contains(handle) {
+            return None;
+        }
+        let Handle(index, _) = handle;
+
+        // swap the node being removed for a Free node
+        let mut temp_node = NodeOption::Free(self.free);
+        mem::swap(&mut self.heap[index], &mut temp_node);
+
+        // that node is now the next free node
+        self.free = Some(index);
+
+        match temp_node {
+            // the only node in the list
+            NodeOption::Occupied { next: None, prev: None, value } => {
+                self.list = Sentry::Empty;
+                Some(value)
+            },
+            // the front node
+            NodeOption::Occupied { next: Some(next), prev: None, value } => {
+                match self.heap[next] {
+                    NodeOption::Occupied { ref mut prev, .. } => *prev = None,
+                    _ => panic!["Free node in list"],
+                };
+                self.list = match self.list {
+                    Sentry::Filled { last, .. } => Sentry::Filled { first: next, last: last },
+                    Sentry::Empty => panic!["Occupied node in empty list"],
+                };
+                Some(value)
+            },
+            // the back node
+            NodeOption::Occupied { next: None, prev: Some(prev), value } => {
+                match self.heap[prev] {
+                    NodeOption::Occupied { ref mut next, .. } => *next = None,
+                    _ => panic!["Free node in list"],
+                };
+                self.list = match self.list {
+                    Sentry::Filled { first, .. } => Sentry::Filled { first: first, last: prev },
+                    Sentry::Empty => panic!["Occupied node in empty list"],
+                };
+                Some(value)
+            },
+            // a node in the middle
+            NodeOption::Occupied { next: Some(next), prev: Some(prev), value } => {
+                match self.heap[prev] {
+                    NodeOption::Occupied { next: ref mut prev_next, .. } => *prev_next = Some(next),
+                    _ => panic!["Free node in list"],
+                };
+                match self.heap[next] {
+                    NodeOption::Occupied { prev: ref mut next_prev, .. } => *next_prev = Some(prev),
+                    _ => panic!["Free node in list"],
+                };
+                Some(value)
+            },
+            NodeOption::Free(..) =>
+                panic!["Unoccupied node in list"],
+        }
+    }
+
+    /// Add an element to the end of the list, evicting the oldest element
+    /// to make room if the list is already at capacity
+    ///
+    /// Returns the evicted element, or `None` if no eviction was needed.
+    /// This never panics, making it suitable for bounded buffers (e.g.
+    /// tracing/log ring buffers) where recent events matter more than old
+    /// ones.
+    pub fn push_overwrite(&mut self, element: T) -> Option<T> {
+        let evicted = if self.free.is_none() {
+            self.dequeue()
+        } else {
+            None
+        };
+        self.enqueue(element);
+        evicted
     }
 
     /// Remove and return an element from the front of the list
     pub fn dequeue(&mut self) -> Option<T> {
         match self.list {
             Sentry::Empty => None,
-            Sentry::Filled { first, last } => {
-                // swap the node being removed for a Free node
-                let mut temp_node = NodeOption::Free(self.free);
-                mem::swap(&mut self.heap[first], &mut temp_node);
-
-                // that node is now the next free node
-                self.free = Some(first);
-
-                // we now process the occupied node and fix the list
-                match temp_node {
-                    // in normal case
-                    NodeOption::Occupied { next: Some(next), prev: None, value } => {
-                        // the next node becomes the new first node
-                        self.list = match self.heap[next] {
-                            NodeOption::Occupied { next: _, ref mut prev, .. } => {
-                                *prev = None;
-                                Sentry::Filled { first: next, last: last }            
-                            },
-                            _ => panic!["Free node in list"],
-                        };
-                        Some(value)
-                    },
-                    // when it was the only node
-                    NodeOption::Occupied { next: None, prev: None, value } => {
-                        // the list becomes empty
-                        self.list = Sentry::Empty;
-                        Some(value)
-                    },
-                    NodeOption::Occupied { .. } =>
-                        panic!["removed node not at front of list"],
-                    NodeOption::Free(..) => 
-                        panic!["Unoccupied node in list"],
-                }
+            Sentry::Filled { first, .. } => {
+                let handle = Handle(first, self.generation[first]);
+                self.remove(handle)
+            },
+        }
+    }
+
+    /// Returns a front-to-back iterator over references to the elements
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            current: match self.list {
+                Sentry::Empty => None,
+                Sentry::Filled { first, .. } => Some(first),
+            },
+        }
+    }
+
+    /// Returns a front-to-back iterator over mutable references to the
+    /// elements
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let current = match self.list {
+            Sentry::Empty => None,
+            Sentry::Filled { first, .. } => Some(first),
+        };
+        IterMut {
+            heap: self.heap.as_mut_slice() as *mut [NodeOption<T>],
+            current: current,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// A front-to-back, borrowing iterator over a `FixedCapacityList`, returned
+/// by `FixedCapacityList::iter`
+pub struct Iter<'a, T: 'a> {
+    list: &'a FixedCapacityList<T>,
+    current: Option<usize>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let index = self.current?;
+        match self.list.heap[index] {
+            NodeOption::Occupied { next, ref value, .. } => {
+                self.current = next;
+                Some(value)
+            },
+            NodeOption::Free(..) => panic!["Free node in list"],
+        }
+    }
+}
+
+/// A front-to-back, mutably-borrowing iterator over a `FixedCapacityList`,
+/// returned by `FixedCapacityList::iter_mut`
+pub struct IterMut<'a, T: 'a> {
+    heap: *mut [NodeOption<T>],
+    current: Option<usize>,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        let index = self.current?;
+        // Safety: the occupied chain visits each slot at most once, so the
+        // mutable reference handed out here never aliases another live one,
+        // and it stays within the list's own borrow since `heap` was derived
+        // from `&mut self` for the lifetime `'a` of this iterator.
+        match unsafe { &mut (*self.heap)[index] } {
+            NodeOption::Occupied { next, value, .. } => {
+                self.current = *next;
+                Some(value)
+            },
+            NodeOption::Free(..) => panic!["Free node in list"],
+        }
+    }
+}
+
+/// An owning, front-to-back iterator over a `FixedCapacityList`, returned by
+/// its `IntoIterator` implementation
+pub struct IntoIter<T>(FixedCapacityList<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.dequeue()
+    }
+}
+
+impl<T> IntoIterator for FixedCapacityList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a FixedCapacityList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut FixedCapacityList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/// A fixed-capacity cache that evicts the least-recently-used entry when
+/// full, built on top of `FixedCapacityList`'s arena and free-list design
+///
+/// List order doubles as recency order, with the most-recently-used entry
+/// at the back, and a `HashMap<K, Handle>` gives O(1) lookup from key to
+/// slot. `get` and `insert` of an existing key move that entry to the back
+/// in O(1) by removing it and re-enqueuing it: the slot just freed is
+/// exactly the one the free list hands back to the very next `enqueue`, so
+/// this is the same move-to-back operation used by Vec-backed LRU lists,
+/// without actually shuffling any other entries. Inserting into a full
+/// buffer evicts the entry at the front.
+pub struct LruBuffer<K, V> {
+    capacity: usize,
+    entries: FixedCapacityList<(K, V)>,
+    index: HashMap<K, Handle>,
+}
+
+impl<K: Eq + Hash, V> LruBuffer<K, V> {
+    /// Creates a new LRU buffer that holds at most `capacity` entries
+    pub fn new(capacity: usize) -> LruBuffer<K, V> {
+        LruBuffer {
+            capacity: capacity,
+            entries: FixedCapacityList::new(capacity),
+            index: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Returns a reference to the least-recently-used entry's value,
+    /// without affecting recency order
+    pub fn peek_lru(&self) -> Option<&V> {
+        let handle = self.entries.front_handle()?;
+        self.entries.get(handle).map(|entry| &entry.1)
+    }
+
+    /// The number of entries currently held
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Whether the buffer holds no entries
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> LruBuffer<K, V> {
+    /// Looks up `key`, marking it as the most-recently-used entry if found
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let handle = *self.index.get(key)?;
+        let new_handle = self.touch(handle);
+        self.index.insert(key.clone(), new_handle);
+        self.entries.get(new_handle).map(|entry| &entry.1)
+    }
+
+    /// Inserts `key`/`value`, marking the entry as most-recently-used
+    ///
+    /// If `key` was already present its value is replaced and the old
+    /// value returned. Otherwise, if the buffer is full, the
+    /// least-recently-used entry is evicted to make room.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&handle) = self.index.get(&key) {
+            let (_, old_value) = self.entries.remove(handle)
+                .expect("LruBuffer index out of sync with its entries");
+            let new_handle = self.entries.enqueue((key.clone(), value));
+            self.index.insert(key, new_handle);
+            return Some(old_value);
+        }
+
+        if self.index.len() == self.capacity {
+            if let Some(lru) = self.entries.front_handle() {
+                let (evicted_key, _) = self.entries.remove(lru)
+                    .expect("LruBuffer index out of sync with its entries");
+                self.index.remove(&evicted_key);
             }
         }
+
+        let handle = self.entries.enqueue((key.clone(), value));
+        self.index.insert(key, handle);
+        None
+    }
+
+    /// Moves the entry at `handle` to the back of the list, returning its
+    /// new handle (the slot freed by `remove` is exactly the one the next
+    /// `enqueue` reuses, so this is O(1))
+    fn touch(&mut self, handle: Handle) -> Handle {
+        let entry = self.entries.remove(handle)
+            .expect("LruBuffer index out of sync with its entries");
+        self.entries.enqueue(entry)
+    }
+}
+
+/// A fixed-capacity priority queue backed by a contiguous `Vec<T>` used as an
+/// implicit binary heap (the parent of index `i` is `(i-1)/2`, its children
+/// `2i+1` and `2i+2`).
+///
+/// Ordering is controlled by a comparator, which defaults to `Ord::cmp` so
+/// the root of the heap is always the greatest element: `push` is an O(log n)
+/// sift-up from the tail and `pop` is an O(log n) sift-down of the root.
+///
+/// Once the buffer is at capacity, `push` compares the incoming element
+/// against the current minimum (always a leaf) using the same comparator:
+/// if the incoming element ranks above that minimum it takes the minimum's
+/// place and the heap is repaired, otherwise the incoming element is handed
+/// back to the caller. This keeps the buffer holding only the top
+/// `capacity` elements seen so far according to the comparator in use.
+pub struct FixedPriorityBuffer<T> {
+    capacity: usize,
+    heap: Vec<T>,
+    compare: Box<dyn Fn(&T, &T) -> Ordering>,
+}
+
+impl<T: Ord> FixedPriorityBuffer<T> {
+    /// Creates a new fixed priority buffer ordered by `Ord::cmp`
+    pub fn new(capacity: usize) -> FixedPriorityBuffer<T> {
+        FixedPriorityBuffer::with_comparator(capacity, |a: &T, b: &T| a.cmp(b))
+    }
+}
+
+impl<T> FixedPriorityBuffer<T> {
+    /// Creates a new fixed priority buffer ordered by the given comparator
+    ///
+    /// Flipping the comparator (e.g. `|a, b| b.cmp(a)`) turns the root into
+    /// the minimum instead of the maximum, which is how a min-heap for
+    /// something like Dijkstra's algorithm is built on top of this type.
+    pub fn with_comparator<F>(capacity: usize, compare: F) -> FixedPriorityBuffer<T>
+        where F: Fn(&T, &T) -> Ordering + 'static
+    {
+        FixedPriorityBuffer {
+            capacity: capacity,
+            heap: Vec::with_capacity(capacity),
+            compare: Box::new(compare),
+        }
+    }
+
+    /// The number of elements currently held
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Whether the buffer holds no elements
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns a reference to the top element without removing it
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.first()
+    }
+
+    /// Insert an element, maintaining heap order
+    ///
+    /// If the buffer is already at capacity, the incoming element is
+    /// compared against the current minimum: if it does not rank above the
+    /// minimum (including when it ties with it) it is returned unchanged,
+    /// otherwise it displaces the minimum, which is returned instead. This is what keeps the buffer holding the top
+    /// `capacity` elements seen so far rather than just the most recent
+    /// ones to arrive.
+    pub fn push(&mut self, element: T) -> Option<T> {
+        if self.heap.len() < self.capacity {
+            self.heap.push(element);
+            let last = self.heap.len() - 1;
+            self.sift_up(last);
+            None
+        } else if self.capacity == 0 {
+            Some(element)
+        } else {
+            let min_index = self.min_leaf_index();
+            if (self.compare)(&element, &self.heap[min_index]) != Ordering::Greater {
+                Some(element)
+            } else {
+                let evicted = mem::replace(&mut self.heap[min_index], element);
+                self.sift_up(min_index);
+                Some(evicted)
+            }
+        }
+    }
+
+    /// Finds the index of the minimum element, which is always among the
+    /// leaves (the second half of the array) since every internal node
+    /// ranks above its children
+    fn min_leaf_index(&self) -> usize {
+        let first_leaf = self.heap.len() / 2;
+        let mut min_index = first_leaf;
+        for i in first_leaf + 1..self.heap.len() {
+            if (self.compare)(&self.heap[i], &self.heap[min_index]) == Ordering::Less {
+                min_index = i;
+            }
+        }
+        min_index
+    }
+
+    /// Remove and return the top element
+    pub fn pop(&mut self) -> Option<T> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let last = self.heap.len() - 1;
+        self.heap.swap(0, last);
+        let popped = self.heap.pop();
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+        popped
+    }
+
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if (self.compare)(&self.heap[index], &self.heap[parent]) == Ordering::Greater {
+                self.heap.swap(index, parent);
+                index = parent;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn sift_down(&mut self, mut index: usize) {
+        let len = self.heap.len();
+        loop {
+            let left = 2 * index + 1;
+            let right = 2 * index + 2;
+            let mut largest = index;
+            if left < len && (self.compare)(&self.heap[left], &self.heap[largest]) == Ordering::Greater {
+                largest = left;
+            }
+            if right < len && (self.compare)(&self.heap[right], &self.heap[largest]) == Ordering::Greater {
+                largest = right;
+            }
+            if largest == index {
+                break;
+            }
+            self.heap.swap(index, largest);
+            index = largest;
+        }
     }
 }
 
@@ -170,5 +626,249 @@ mod tests {
         assert_eq![b.dequeue(), Some(3)];
         assert_eq![b.dequeue(), Some(4)];
     }
+
+    #[test]
+    fn push_overwrite_does_not_evict_below_capacity() {
+        let mut b = FixedCapacityList::<i32>::new(2);
+        assert_eq!(b.push_overwrite(1), None);
+        assert_eq!(b.push_overwrite(2), None);
+        assert_eq!(b.dequeue(), Some(1));
+        assert_eq!(b.dequeue(), Some(2));
+    }
+
+    #[test]
+    fn push_overwrite_evicts_oldest_when_full() {
+        let mut b = FixedCapacityList::<i32>::new(2);
+        b.push_overwrite(1);
+        b.push_overwrite(2);
+        assert_eq!(b.push_overwrite(3), Some(1));
+        assert_eq!(b.dequeue(), Some(2));
+        assert_eq!(b.dequeue(), Some(3));
+        assert_eq!(b.dequeue(), None);
+    }
+
+    #[test]
+    fn remove_unlinks_a_node_from_the_middle() {
+        let mut b = FixedCapacityList::<i32>::new(3);
+        b.enqueue(1);
+        let middle = b.enqueue(2);
+        b.enqueue(3);
+        assert_eq!(b.remove(middle), Some(2));
+        assert_eq!(b.dequeue(), Some(1));
+        assert_eq!(b.dequeue(), Some(3));
+        assert_eq!(b.dequeue(), None);
+    }
+
+    #[test]
+    fn remove_unlinks_the_back_node() {
+        let mut b = FixedCapacityList::<i32>::new(3);
+        b.enqueue(1);
+        b.enqueue(2);
+        let back = b.enqueue(3);
+        assert_eq!(b.remove(back), Some(3));
+        assert_eq!(b.dequeue(), Some(1));
+        assert_eq!(b.dequeue(), Some(2));
+        assert_eq!(b.dequeue(), None);
+    }
+
+    #[test]
+    fn removed_handle_becomes_invalid() {
+        let mut b = FixedCapacityList::<i32>::new(2);
+        let handle = b.enqueue(1);
+        assert!(b.contains(handle));
+        assert_eq!(b.remove(handle), Some(1));
+        assert!(!b.contains(handle));
+        assert_eq!(b.remove(handle), None);
+    }
+
+    #[test]
+    fn stale_handle_is_not_confused_with_reused_slot() {
+        let mut b = FixedCapacityList::<i32>::new(1);
+        let first = b.enqueue(1);
+        b.dequeue();
+        let second = b.enqueue(2);
+        assert!(!b.contains(first));
+        assert_eq!(b.remove(first), None);
+        assert!(b.contains(second));
+        assert_eq!(b.dequeue(), Some(2));
+    }
+
+    #[test]
+    fn iter_walks_front_to_back() {
+        let mut b = FixedCapacityList::<i32>::new(3);
+        b.enqueue(1);
+        b.enqueue(2);
+        b.enqueue(3);
+        let collected: Vec<&i32> = b.iter().collect();
+        assert_eq!(collected, vec![&1, &2, &3]);
+    }
+
+    #[test]
+    fn iter_mut_allows_updating_elements_in_place() {
+        let mut b = FixedCapacityList::<i32>::new(3);
+        b.enqueue(1);
+        b.enqueue(2);
+        b.enqueue(3);
+        for value in b.iter_mut() {
+            *value *= 10;
+        }
+        let collected: Vec<&i32> = b.iter().collect();
+        assert_eq!(collected, vec![&10, &20, &30]);
+    }
+
+    #[test]
+    fn into_iter_drains_the_list_in_order() {
+        let mut b = FixedCapacityList::<i32>::new(3);
+        b.enqueue(1);
+        b.enqueue(2);
+        b.enqueue(3);
+        let collected: Vec<i32> = b.into_iter().collect();
+        assert_eq!(collected, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn for_loop_works_via_borrowed_intoiterator() {
+        let mut b = FixedCapacityList::<i32>::new(2);
+        b.enqueue(1);
+        b.enqueue(2);
+        let mut sum = 0;
+        for value in &b {
+            sum += value;
+        }
+        assert_eq!(sum, 3);
+    }
+}
+
+#[cfg(test)]
+mod priority_buffer_tests {
+    use super::FixedPriorityBuffer;
+
+    #[test]
+    fn pops_in_descending_priority_order() {
+        let mut b = FixedPriorityBuffer::<i32>::new(4);
+        b.push(3);
+        b.push(1);
+        b.push(4);
+        b.push(2);
+        assert_eq!(b.pop(), Some(4));
+        assert_eq!(b.pop(), Some(3));
+        assert_eq!(b.pop(), Some(2));
+        assert_eq!(b.pop(), Some(1));
+        assert_eq!(b.pop(), None);
+    }
+
+    #[test]
+    fn peek_returns_top_without_removing() {
+        let mut b = FixedPriorityBuffer::<i32>::new(2);
+        b.push(1);
+        b.push(5);
+        assert_eq!(b.peek(), Some(&5));
+        assert_eq!(b.len(), 2);
+    }
+
+    #[test]
+    fn push_rejects_when_full_and_not_better_than_minimum() {
+        let mut b = FixedPriorityBuffer::<i32>::new(2);
+        b.push(5);
+        b.push(3);
+        assert_eq!(b.push(1), Some(1));
+        assert_eq!(b.len(), 2);
+    }
+
+    #[test]
+    fn push_retains_the_top_k_elements_seen_so_far() {
+        let mut b = FixedPriorityBuffer::<i32>::new(3);
+        for element in [1, 2, 3, 4, 5, 6] {
+            b.push(element);
+        }
+        assert_eq!(b.pop(), Some(6));
+        assert_eq!(b.pop(), Some(5));
+        assert_eq!(b.pop(), Some(4));
+        assert_eq!(b.pop(), None);
+    }
+
+    #[test]
+    fn with_comparator_builds_a_min_heap() {
+        let mut b = FixedPriorityBuffer::with_comparator(3, |a: &i32, b: &i32| {
+            b.cmp(a)
+        });
+        b.push(5);
+        b.push(1);
+        b.push(3);
+        assert_eq!(b.pop(), Some(1));
+        assert_eq!(b.pop(), Some(3));
+        assert_eq!(b.pop(), Some(5));
+    }
+
+    #[test]
+    fn with_comparator_can_order_by_key() {
+        #[derive(Debug, PartialEq)]
+        struct Edge { cost: i32 }
+
+        let mut b = FixedPriorityBuffer::with_comparator(2, |a: &Edge, b: &Edge| {
+            // flipped so the lowest cost sits at the root, as in Dijkstra
+            b.cost.cmp(&a.cost)
+        });
+        b.push(Edge { cost: 10 });
+        b.push(Edge { cost: 2 });
+        assert_eq!(b.pop(), Some(Edge { cost: 2 }));
+        assert_eq!(b.pop(), Some(Edge { cost: 10 }));
+    }
+}
+
+#[cfg(test)]
+mod lru_buffer_tests {
+    use super::LruBuffer;
+
+    #[test]
+    fn get_and_insert_round_trip() {
+        let mut b = LruBuffer::<&str, i32>::new(2);
+        b.insert("a", 1);
+        b.insert("b", 2);
+        assert_eq!(b.get(&"a"), Some(&1));
+        assert_eq!(b.get(&"b"), Some(&2));
+        assert_eq!(b.get(&"c"), None);
+    }
+
+    #[test]
+    fn insert_evicts_the_least_recently_used_entry_when_full() {
+        let mut b = LruBuffer::<&str, i32>::new(2);
+        b.insert("a", 1);
+        b.insert("b", 2);
+        // touch "a" so "b" becomes the least-recently-used entry
+        b.get(&"a");
+        b.insert("c", 3);
+        assert_eq!(b.get(&"b"), None);
+        assert_eq!(b.get(&"a"), Some(&1));
+        assert_eq!(b.get(&"c"), Some(&3));
+    }
+
+    #[test]
+    fn insert_on_existing_key_replaces_value_and_returns_old() {
+        let mut b = LruBuffer::<&str, i32>::new(2);
+        b.insert("a", 1);
+        assert_eq!(b.insert("a", 2), Some(1));
+        assert_eq!(b.get(&"a"), Some(&2));
+        assert_eq!(b.len(), 1);
+    }
+
+    #[test]
+    fn peek_lru_reports_oldest_entry_without_touching_it() {
+        let mut b = LruBuffer::<&str, i32>::new(2);
+        b.insert("a", 1);
+        b.insert("b", 2);
+        assert_eq!(b.peek_lru(), Some(&1));
+        b.insert("c", 3);
+        assert_eq!(b.get(&"a"), None);
+    }
+
+    #[test]
+    fn len_and_is_empty_track_entry_count() {
+        let mut b = LruBuffer::<&str, i32>::new(2);
+        assert!(b.is_empty());
+        b.insert("a", 1);
+        assert_eq!(b.len(), 1);
+        assert!(!b.is_empty());
+    }
 }
 